@@ -0,0 +1,189 @@
+use paths::coords::{Point, Vector};
+use paths::curve_approx::CurveInfo;
+
+/// Number of sub-intervals used when building the monotonic arc-length
+/// table that brackets the Newton/bisection search in [`EllipseSegment::t_at_length`].
+const LENGTH_TABLE_STEPS: usize = 64;
+
+/// Number of Simpson sub-intervals used for each length evaluation during
+/// the `t(s)` search. Coarser than the table above since it only has to
+/// refine a single bracket, not cover the whole arc.
+const REFINE_STEPS: usize = 16;
+
+/// An elliptical arc, parametrized as `R(rot) * (rx*cos(t), ry*sin(t))` for
+/// `t` in `[start, end]`, where `R(rot)` is the rotation by the ellipse's
+/// x-axis rotation angle.
+///
+/// Unlike [`curves::circle_segment::CircleSegment`], an ellipse has no
+/// closed form for its arc length, so `length()` and the `t(s)` inversion
+/// used by `value()` are both computed numerically.
+pub struct EllipseSegment {
+    rx: f64,
+    ry: f64,
+    start: f64,
+    end: f64,
+    rot: f64,
+    /// Cumulative arc length from `start` at `LENGTH_TABLE_STEPS + 1` evenly
+    /// spaced parameter values, used to bracket the search in `t_at_length`.
+    length_table: Vec<f64>,
+}
+
+impl EllipseSegment {
+    pub fn new(rx: f64, ry: f64, start: f64, end: f64, rot: f64) -> EllipseSegment {
+        let length_table = Self::build_length_table(rx, ry, start, end);
+        EllipseSegment {
+            rx,
+            ry,
+            start,
+            end,
+            rot,
+            length_table,
+        }
+    }
+
+    /// `|d/dt (rx*cos t, ry*sin t)|`. The rotation by `rot` is a rigid
+    /// transform, so it does not affect the speed.
+    fn speed(rx: f64, ry: f64, t: f64) -> f64 {
+        let dx = -rx * t.sin();
+        let dy = ry * t.cos();
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Arc length (always non-negative) between parameters `a` and `b` via a
+    /// single Simpson's rule application, refined by splitting into `steps`
+    /// sub-intervals. `speed()` is never negative, so the traveled distance
+    /// is the integral of its magnitude regardless of whether `b < a` (as
+    /// happens for a counter-clockwise-swept arc, where `end < start`).
+    fn integrate(rx: f64, ry: f64, a: f64, b: f64, steps: usize) -> f64 {
+        let dt = (b - a) / steps as f64;
+        let mut acc = 0.0;
+        for i in 0..steps {
+            let t0 = a + dt * i as f64;
+            let t1 = t0 + dt;
+            let tm = 0.5 * (t0 + t1);
+            acc += (dt.abs() / 6.0)
+                * (Self::speed(rx, ry, t0) + 4.0 * Self::speed(rx, ry, tm) + Self::speed(rx, ry, t1));
+        }
+        acc
+    }
+
+    fn build_length_table(rx: f64, ry: f64, start: f64, end: f64) -> Vec<f64> {
+        let dt = (end - start) / LENGTH_TABLE_STEPS as f64;
+        let mut table = Vec::with_capacity(LENGTH_TABLE_STEPS + 1);
+        table.push(0.0);
+        let mut acc = 0.0;
+        for i in 0..LENGTH_TABLE_STEPS {
+            let t0 = start + dt * i as f64;
+            acc += Self::integrate(rx, ry, t0, t0 + dt, 1);
+            table.push(acc);
+        }
+        table
+    }
+
+    /// Length of the arc from `start` to `t`, for a `t` inside the table's
+    /// `idx`-th bracket: the table's length up to that bracket plus a finer
+    /// integration over the part of the bracket up to `t`.
+    fn length_from_start(&self, idx: usize, t: f64) -> f64 {
+        let dt = (self.end - self.start) / (self.length_table.len() - 1) as f64;
+        let seg_a = self.start + dt * idx as f64;
+        self.length_table[idx] + Self::integrate(self.rx, self.ry, seg_a, t, REFINE_STEPS)
+    }
+
+    /// Find the parameter `t` at which the arc length from `start` equals
+    /// `s`, by bracketing `s` in `length_table` and refining with Newton's
+    /// method, falling back to bisection whenever a Newton step would leave
+    /// the bracket.
+    ///
+    /// `length_from_start` is monotonic in the *signed* direction from
+    /// `start` towards `end`, which runs opposite to increasing `t` whenever
+    /// `end < start` (every counter-clockwise/`sweep-flag=0` SVG arc). `dir`
+    /// tracks that sign so both the bracket update and the Newton derivative
+    /// stay correct for either direction.
+    fn t_at_length(&self, s: f64) -> f64 {
+        let n = self.length_table.len() - 1;
+        let total = *self.length_table.last().unwrap();
+        let s = s.clamp(0.0, total);
+
+        let idx = self.length_table.partition_point(|&l| l < s).clamp(1, n) - 1;
+        let dt = (self.end - self.start) / n as f64;
+        let dir = if dt >= 0.0 { 1.0 } else { -1.0 };
+        let seg_a = self.start + dt * idx as f64;
+        let seg_b = seg_a + dt;
+        let mut lo = seg_a.min(seg_b);
+        let mut hi = seg_a.max(seg_b);
+        let mut t = seg_a
+            + (seg_b - seg_a) * (s - self.length_table[idx])
+                / (self.length_table[idx + 1] - self.length_table[idx]).max(f64::EPSILON);
+
+        for _ in 0..8 {
+            let f = self.length_from_start(idx, t) - s;
+            if (f > 0.0) == (dir > 0.0) {
+                hi = t;
+            } else {
+                lo = t;
+            }
+            let deriv = dir * Self::speed(self.rx, self.ry, t);
+            if deriv.abs() < f64::EPSILON {
+                break;
+            }
+            let newton_t = t - f / deriv;
+            t = if newton_t > lo && newton_t < hi {
+                newton_t
+            } else {
+                0.5 * (lo + hi)
+            };
+        }
+        t
+    }
+}
+
+impl CurveInfo for EllipseSegment {
+    fn length(&self) -> f64 {
+        *self.length_table.last().unwrap()
+    }
+
+    fn value(&self, s: f64) -> (Point, Vector) {
+        let t = self.t_at_length(s);
+
+        let (sin_r, cos_r) = self.rot.sin_cos();
+        let (sin_t, cos_t) = t.sin_cos();
+        let (sin_s, cos_s) = self.start.sin_cos();
+
+        // Relative to the arc's own start, matching how the other
+        // `CurveInfo` implementations report their position.
+        let px = self.rx * (cos_t - cos_s);
+        let py = self.ry * (sin_t - sin_s);
+        let point = Point {
+            x: px * cos_r - py * sin_r,
+            y: px * sin_r + py * cos_r,
+        };
+
+        let dx = -self.rx * sin_t;
+        let dy = self.ry * cos_t;
+        let tangent = Vector {
+            x: dx * cos_r - dy * sin_r,
+            y: dx * sin_r + dy * cos_r,
+        }
+        .unit();
+
+        (point, tangent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A counter-clockwise SVG sweep (`sweep-flag = 0`) produces `end <
+    /// start`. `length()` must still come out positive and `value()` must
+    /// not panic when clamping against it.
+    #[test]
+    fn handles_decreasing_parameter_range() {
+        let ellipse = EllipseSegment::new(2.0, 1.0, std::f64::consts::PI, 0.0, 0.0);
+        let length = ellipse.length();
+        assert!(length > 0.0);
+        let _ = ellipse.value(length);
+        let _ = ellipse.value(0.0);
+        let _ = ellipse.value(0.5 * length);
+    }
+}
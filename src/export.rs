@@ -0,0 +1,367 @@
+use byteorder::{LittleEndian as LE, WriteBytesExt};
+use paths::coords::{Point, Vector};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Output format for a cam profile, selected by the output file's extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Svg,
+    Pdf,
+    PostScript,
+    LDraw,
+    Stl,
+    Dxf,
+}
+
+impl FileFormat {
+    /// Guess the format from a file's extension: `.svg`, `.pdf`, `.ps`,
+    /// `.dat`/`.ldr` (LDraw), `.stl` or `.dxf`.
+    pub fn from_path(path: &Path) -> Option<FileFormat> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "svg" => Some(FileFormat::Svg),
+            "pdf" => Some(FileFormat::Pdf),
+            "ps" => Some(FileFormat::PostScript),
+            "dat" | "ldr" => Some(FileFormat::LDraw),
+            "stl" => Some(FileFormat::Stl),
+            "dxf" => Some(FileFormat::Dxf),
+            _ => None,
+        }
+    }
+}
+
+/// Something that can write itself out as any of the supported
+/// [`FileFormat`]s.
+pub trait Export {
+    fn export(&self, writer: &mut dyn Write, format: FileFormat) -> io::Result<()>;
+}
+
+/// The two cam profiles (one per follower), ready to be exported. Each is a
+/// set of simple boundary loops, since winding-rule cleanup can split a
+/// self-intersecting profile into more than one loop.
+pub struct CamProfile {
+    pub path1: Vec<Vec<Point>>,
+    pub path2: Vec<Vec<Point>>,
+}
+
+impl Export for CamProfile {
+    fn export(&self, writer: &mut dyn Write, format: FileFormat) -> io::Result<()> {
+        match format {
+            FileFormat::Svg => export_svg(writer, &self.path1, &self.path2),
+            FileFormat::Pdf => export_pdf(writer, &self.path1, &self.path2),
+            FileFormat::PostScript => export_ps(writer, &self.path1, &self.path2),
+            FileFormat::LDraw => export_ldraw(writer, &self.path1, &self.path2),
+            FileFormat::Stl => export_stl(writer, &self.path1, &self.path2),
+            FileFormat::Dxf => export_dxf(writer, &self.path1, &self.path2),
+        }
+    }
+}
+
+pub fn svg_prologue<W: Write + ?Sized>(w: &mut W) -> io::Result<usize> {
+    let width = 100.0;
+    let height = 100.0;
+    w.write(
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<svg xmlns="http://www.w3.org/2000/svg"
+     width="{width}mm" height="{height}mm" viewBox="{} {} {height} {height}">
+"#,
+            -height / 2.0,
+            -width / 2.0
+        )
+        .as_bytes(),
+    )
+}
+
+pub fn svg_epilogue<W: Write + ?Sized>(w: &mut W) -> io::Result<usize> {
+    w.write(b"</svg>\n")
+}
+
+fn export_svg(
+    writer: &mut dyn Write,
+    path1: &[Vec<Point>],
+    path2: &[Vec<Point>],
+) -> io::Result<()> {
+    svg_prologue(writer)?;
+    for path in path1.iter().chain(path2) {
+        write!(writer, "<path style=\"fill:none;stroke:black\" d=\"M")?;
+        for p in path {
+            write!(writer, " {}, {}", p.x, p.y)?;
+        }
+        writeln!(writer, " z\"/>")?;
+    }
+    svg_epilogue(writer)?;
+    Ok(())
+}
+
+fn write_ps_like_path(
+    writer: &mut dyn Write,
+    path: &[Point],
+    moveto: &str,
+    lineto: &str,
+) -> io::Result<()> {
+    let mut points = path.iter();
+    if let Some(p) = points.next() {
+        writeln!(writer, "{:.3} {:.3} {moveto}", p.x, p.y)?;
+        for p in points {
+            writeln!(writer, "{:.3} {:.3} {lineto}", p.x, p.y)?;
+        }
+    }
+    Ok(())
+}
+
+fn export_ps(
+    writer: &mut dyn Write,
+    path1: &[Vec<Point>],
+    path2: &[Vec<Point>],
+) -> io::Result<()> {
+    writeln!(writer, "%!PS-Adobe-3.0")?;
+    writeln!(writer, "%%BoundingBox: -500 -500 500 500")?;
+    writeln!(writer, "1 setlinewidth")?;
+    for path in path1.iter().chain(path2) {
+        writeln!(writer, "newpath")?;
+        write_ps_like_path(writer, path, "moveto", "lineto")?;
+        writeln!(writer, "closepath")?;
+        writeln!(writer, "stroke")?;
+    }
+    writeln!(writer, "showpage")?;
+    writeln!(writer, "%%EOF")?;
+    Ok(())
+}
+
+fn export_pdf(
+    writer: &mut dyn Write,
+    path1: &[Vec<Point>],
+    path2: &[Vec<Point>],
+) -> io::Result<()> {
+    let mut content = Vec::new();
+    writeln!(content, "1 w")?;
+    // The cam loops are centered on the origin, like the PostScript writer's
+    // symmetric BoundingBox; translate them to the MediaBox center instead.
+    writeln!(content, "1 0 0 1 500 500 cm")?;
+    for path in path1.iter().chain(path2) {
+        write_ps_like_path(&mut content, path, "m", "l")?;
+        writeln!(content, "h S")?;
+    }
+
+    let mut content_obj = Vec::new();
+    write!(content_obj, "<< /Length {} >>\nstream\n", content.len())?;
+    content_obj.extend_from_slice(&content);
+    content_obj.extend_from_slice(b"\nendstream");
+
+    let objects: [&[u8]; 4] = [
+        b"<< /Type /Catalog /Pages 2 0 R >>",
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>",
+        b"<< /Type /Page /Parent 2 0 R /MediaBox [0 0 1000 1000] /Contents 4 0 R >>",
+        &content_obj,
+    ];
+    write_pdf(writer, &objects)
+}
+
+/// Write a minimal single-page PDF with numbered objects `1..=objects.len()`
+/// and a cross-reference table, computing each object's byte offset as it
+/// is assembled.
+fn write_pdf(writer: &mut dyn Write, objects: &[&[u8]]) -> io::Result<()> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, obj) in objects.iter().enumerate() {
+        offsets.push(buf.len());
+        writeln!(buf, "{} 0 obj", i + 1)?;
+        buf.extend_from_slice(obj);
+        buf.extend_from_slice(b"\nendobj\n");
+    }
+    let xref_offset = buf.len();
+    write!(buf, "xref\n0 {}\n", objects.len() + 1)?;
+    write!(buf, "0000000000 65535 f \r\n")?;
+    for offset in &offsets {
+        write!(buf, "{offset:010} 00000 n \r\n")?;
+    }
+    write!(
+        buf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    )?;
+
+    writer.write_all(&buf)
+}
+
+struct LdrawCoord {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl LdrawCoord {
+    fn xy_z(xy: &Point, z: f64) -> LdrawCoord {
+        LdrawCoord { x: xy.x, y: xy.y, z }
+    }
+}
+
+impl std::fmt::Display for LdrawCoord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.3} {:.3} {:.3}", self.x, self.y, self.z)
+    }
+}
+
+fn export_ldraw(
+    writer: &mut dyn Write,
+    path1: &[Vec<Point>],
+    path2: &[Vec<Point>],
+) -> io::Result<()> {
+    writeln!(writer, "0 BFC CERTIFY CCW")?;
+    for path in path1.iter().chain(path2) {
+        write_ldraw_loop(writer, path)?;
+    }
+    Ok(())
+}
+
+fn write_ldraw_loop(out: &mut dyn Write, path: &[Point]) -> io::Result<()> {
+    let lower = 0.0;
+    let upper = 20.0;
+    let scale = 20.0 / 8.0;
+    let radius = 6.0;
+    if let Some(mut prev) = path.last().map(|p| *p * scale) {
+        for p in path {
+            let p = *p * scale;
+            let c = p * (radius / p.length());
+            let prev_c = prev * (radius / prev.length());
+            writeln!(
+                out,
+                "4 16 {} {} {} {}",
+                LdrawCoord::xy_z(&prev, upper),
+                LdrawCoord::xy_z(&p, upper),
+                LdrawCoord::xy_z(&p, lower),
+                LdrawCoord::xy_z(&prev, lower),
+            )?;
+            writeln!(
+                out,
+                "4 16 {} {} {} {}",
+                LdrawCoord::xy_z(&prev, upper),
+                LdrawCoord::xy_z(&prev_c, upper),
+                LdrawCoord::xy_z(&c, upper),
+                LdrawCoord::xy_z(&p, upper),
+            )?;
+            writeln!(
+                out,
+                "4 16 {} {} {} {}",
+                LdrawCoord::xy_z(&prev, lower),
+                LdrawCoord::xy_z(&prev_c, lower),
+                LdrawCoord::xy_z(&c, lower),
+                LdrawCoord::xy_z(&p, lower),
+            )?;
+            writeln!(
+                out,
+                "4 16 {} {} {} {}",
+                LdrawCoord::xy_z(&prev_c, upper),
+                LdrawCoord::xy_z(&prev_c, lower),
+                LdrawCoord::xy_z(&c, lower),
+                LdrawCoord::xy_z(&c, upper),
+            )?;
+            prev = p;
+        }
+    }
+    Ok(())
+}
+
+fn write_stl_xy_z(out: &mut dyn Write, xy: &Vector, z: f64) -> io::Result<()> {
+    out.write_f32::<LE>(xy.x as f32)?;
+    out.write_f32::<LE>(xy.y as f32)?;
+    out.write_f32::<LE>(z as f32)?;
+    Ok(())
+}
+
+fn write_stl_triangle(
+    out: &mut dyn Write,
+    normal: &(Vector, f64),
+    vertices: &[(Vector, f64); 3],
+) -> io::Result<()> {
+    write_stl_xy_z(out, &normal.0, normal.1)?;
+    for (xy, z) in vertices {
+        write_stl_xy_z(out, xy, *z)?;
+    }
+    out.write_u16::<LE>(0)?;
+    Ok(())
+}
+
+fn write_stl_quad(
+    out: &mut dyn Write,
+    normal: &(Vector, f64),
+    vertices: &[(Vector, f64); 4],
+) -> io::Result<()> {
+    write_stl_triangle(out, normal, &[vertices[0], vertices[1], vertices[2]])?;
+    write_stl_triangle(out, normal, &[vertices[2], vertices[3], vertices[0]])
+}
+
+fn export_stl(
+    writer: &mut dyn Write,
+    path1: &[Vec<Point>],
+    path2: &[Vec<Point>],
+) -> io::Result<()> {
+    let header = [0u8; 80];
+    writer.write_all(&header)?;
+    let point_count: usize = path1.iter().chain(path2).map(Vec::len).sum();
+    writer.write_u32::<LE>((point_count * (2 * 2)) as u32)?;
+    for path in path1.iter().chain(path2) {
+        write_stl_loop(writer, path)?;
+    }
+    Ok(())
+}
+
+fn write_stl_loop(out: &mut dyn Write, path: &[Point]) -> io::Result<()> {
+    let lower = 0.0;
+    let upper = 8.0;
+    let radius = 6.0;
+    if let Some(mut prev) = path.last().copied() {
+        for p in path {
+            let p = *p;
+            let c = p * (radius / p.length());
+            let prev_c = prev * (radius / prev.length());
+            write_stl_quad(
+                out,
+                &(p, 0.0),
+                &[(prev, lower), (p, lower), (p, upper), (prev, upper)],
+            )?;
+            write_stl_quad(
+                out,
+                &(Vector { x: 0.0, y: 0.0 }, 1.0),
+                &[(p, upper), (c, upper), (prev_c, upper), (prev, upper)],
+            )?;
+            prev = p;
+        }
+    }
+    Ok(())
+}
+
+/// Write every loop of the two cam profiles as a closed `LWPOLYLINE` entity,
+/// with one layer per profile, in a bare-bones `ENTITIES`-only DXF (no `HEADER`/`TABLES`
+/// sections; readers default undeclared layers to the usual "0" look).
+/// Coordinates are written as-is, in the same millimetre-scaled unit as the
+/// SVG/STL output.
+fn export_dxf(
+    writer: &mut dyn Write,
+    path1: &[Vec<Point>],
+    path2: &[Vec<Point>],
+) -> io::Result<()> {
+    writeln!(writer, "0\nSECTION\n2\nENTITIES")?;
+    for path in path1 {
+        write_dxf_polyline(writer, "CAM_1", path)?;
+    }
+    for path in path2 {
+        write_dxf_polyline(writer, "CAM_2", path)?;
+    }
+    writeln!(writer, "0\nENDSEC\n0\nEOF")?;
+    Ok(())
+}
+
+fn write_dxf_polyline(out: &mut dyn Write, layer: &str, path: &[Point]) -> io::Result<()> {
+    writeln!(out, "0\nLWPOLYLINE")?;
+    writeln!(out, "8\n{layer}")?;
+    writeln!(out, "90\n{}", path.len())?;
+    writeln!(out, "70\n1")?; // closed polyline
+    for p in path {
+        writeln!(out, "10\n{:.3}\n20\n{:.3}", p.x, p.y)?;
+    }
+    Ok(())
+}
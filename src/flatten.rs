@@ -0,0 +1,137 @@
+use paths::curve_approx::CurveInfo;
+
+/// Default flatness tolerance, in studs: the maximum perpendicular deviation
+/// a curve may have from the straight chord approximating it before it gets
+/// subdivided further.
+pub const DEFAULT_TOLERANCE: f64 = 0.05;
+
+/// Deepest recursion allowed per top-level interval, bounding the output
+/// size even for a cusp the tolerance can never be satisfied at.
+const MAX_DEPTH: u32 = 16;
+
+/// Adaptively sample `curve` between arc lengths `0` and `curve.length()`,
+/// returning the arc-length positions of a non-uniform point list such that
+/// no interval between consecutive positions deviates from its chord by
+/// more than `tolerance`, as measured at the interval's midpoint (below).
+/// An interval still over `tolerance` after `MAX_DEPTH` halvings is emitted
+/// as it is.
+///
+/// Each interval is tested by evaluating the curve at its midpoint and
+/// measuring the perpendicular distance from that point to the chord
+/// between its endpoints (for a circular arc this is exactly the sagitta
+/// `r(1-cos(dtheta/2))`; for a Bezier it plays the same role as the usual
+/// control-point flatness test). If the deviation exceeds `tolerance`, the
+/// interval is split in half and both halves are refined recursively;
+/// otherwise only its end position is emitted. The returned positions
+/// always start with `0.0`.
+///
+/// `breaks` are extra arc-length positions (e.g. the joints between the
+/// underlying segments of a `ConcatCurve`) that are always emitted exactly,
+/// regardless of deviation: the midpoint deviation test can't see a kink
+/// sitting exactly at an interval's boundary, so segment joints must be
+/// forced rather than left to the heuristic.
+pub fn flatten(curve: &dyn CurveInfo, tolerance: f64, breaks: &[f64]) -> Vec<f64> {
+    let mut positions = vec![0.0];
+    let total = curve.length();
+    let mut bounds: Vec<f64> = breaks.iter().copied().filter(|b| *b > 0.0 && *b < total).collect();
+    bounds.push(total);
+    bounds.sort_by(f64::total_cmp);
+    bounds.dedup();
+
+    let mut s0 = 0.0;
+    for s1 in bounds {
+        subdivide(curve, s0, s1, tolerance, MAX_DEPTH, &mut positions);
+        s0 = s1;
+    }
+    positions
+}
+
+fn subdivide(
+    curve: &dyn CurveInfo,
+    s0: f64,
+    s1: f64,
+    tolerance: f64,
+    depth: u32,
+    positions: &mut Vec<f64>,
+) {
+    if depth > 0 && deviation(curve, s0, s1) > tolerance {
+        let mid = 0.5 * (s0 + s1);
+        subdivide(curve, s0, mid, tolerance, depth - 1, positions);
+        subdivide(curve, mid, s1, tolerance, depth - 1, positions);
+    } else {
+        positions.push(s1);
+    }
+}
+
+/// Perpendicular distance from the curve's point at the midpoint of `[s0,
+/// s1]` to the chord between the curve's points at `s0` and `s1`.
+fn deviation(curve: &dyn CurveInfo, s0: f64, s1: f64) -> f64 {
+    let (p0, _) = curve.value(s0);
+    let (p1, _) = curve.value(s1);
+    let (pm, _) = curve.value(0.5 * (s0 + s1));
+
+    let chord = p1 - p0;
+    let chord_len = chord.length();
+    let to_mid = pm - p0;
+    if chord_len < f64::EPSILON {
+        return to_mid.length();
+    }
+    (chord.x * to_mid.y - chord.y * to_mid.x).abs() / chord_len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paths::coords::{Point, Vector};
+
+    /// A two-segment polyline with a right-angle kink just past its start: a
+    /// long, straight second segment means the single midpoint sample taken
+    /// across the whole curve lands on that second segment and is nearly
+    /// collinear with the overall chord, so `deviation` alone doesn't see
+    /// the corner at all.
+    struct KinkedPolyline {
+        corner_len: f64,
+        total_len: f64,
+    }
+
+    impl CurveInfo for KinkedPolyline {
+        fn length(&self) -> f64 {
+            self.total_len
+        }
+
+        fn value(&self, s: f64) -> (Point, Vector) {
+            if s <= self.corner_len {
+                (Point { x: s, y: 0.0 }, Vector { x: 1.0, y: 0.0 })
+            } else {
+                let u = s - self.corner_len;
+                (
+                    Point {
+                        x: self.corner_len,
+                        y: u,
+                    },
+                    Vector { x: 0.0, y: 1.0 },
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn midpoint_deviation_alone_misses_a_near_start_kink() {
+        let curve = KinkedPolyline {
+            corner_len: 0.01,
+            total_len: 2.0,
+        };
+        let positions = flatten(&curve, DEFAULT_TOLERANCE, &[]);
+        assert_eq!(positions, vec![0.0, 2.0]);
+    }
+
+    #[test]
+    fn forced_break_captures_the_kink() {
+        let curve = KinkedPolyline {
+            corner_len: 0.01,
+            total_len: 2.0,
+        };
+        let positions = flatten(&curve, DEFAULT_TOLERANCE, &[0.01]);
+        assert!(positions.contains(&0.01));
+    }
+}
@@ -0,0 +1,143 @@
+use paths::coords::{Point, Vector};
+
+/// Above this ratio of miter length to offset distance, a vertex join falls
+/// back from a miter to a round join.
+const MITER_LIMIT: f64 = 2.0;
+
+/// Number of steps a round join's arc is split into; the arc gets one more
+/// point than this.
+const ROUND_JOIN_STEPS: usize = 4;
+
+/// Inflate a closed polygon loop by `distance`, in the direction each edge's
+/// `rotate_90_ccw()` normal points.
+///
+/// Each edge is offset to a parallel line at `distance`, and consecutive
+/// offset edges are joined by intersecting them (a miter join). When the
+/// miter point would land further than `MITER_LIMIT * distance` from the
+/// original vertex, a round join is inserted instead: an arc of points at
+/// `distance` from the vertex, between the two offset edge ends. The result
+/// can still self-intersect around concave regions or tight curvature;
+/// resolving that is left to [`crate::winding::cleanup_loop`], which the
+/// caller runs on this function's output under the chosen fill rule.
+pub fn offset_loop(loop_: &[Point], distance: f64) -> Vec<Point> {
+    let n = loop_.len();
+    if n < 3 {
+        return loop_.to_vec();
+    }
+
+    let edges: Vec<(Point, Point)> = (0..n)
+        .map(|i| {
+            let p0 = loop_[i];
+            let p1 = loop_[(i + 1) % n];
+            let normal = (p1 - p0).unit().rotate_90_ccw() * distance;
+            let mut a = p0;
+            a += normal;
+            let mut b = p1;
+            b += normal;
+            (a, b)
+        })
+        .collect();
+
+    let mut result = Vec::with_capacity(n);
+    for i in 0..n {
+        let prev = (i + n - 1) % n;
+        let (prev_start, prev_end) = edges[prev];
+        let (cur_start, cur_end) = edges[i];
+        let d_prev = (prev_end - prev_start).unit();
+        let d_cur = (cur_end - cur_start).unit();
+
+        let miter = line_intersection(prev_start, d_prev, cur_start, d_cur)
+            .filter(|joint| (*joint - loop_[i]).length() <= MITER_LIMIT * distance);
+
+        if let Some(joint) = miter {
+            result.push(joint);
+        } else {
+            result.extend(round_join(loop_[i], prev_end, cur_start, distance));
+        }
+    }
+    result
+}
+
+/// Arc of points at `distance` from `center`, from `from` to `to`, taking
+/// the short way around (a join never needs to turn more than half a turn).
+fn round_join(center: Point, from: Point, to: Point, distance: f64) -> Vec<Point> {
+    let v0 = from - center;
+    let v1 = to - center;
+    let start_angle = v0.y.atan2(v0.x);
+    let end_angle = v1.y.atan2(v1.x);
+    let mut delta = end_angle - start_angle;
+    if delta > std::f64::consts::PI {
+        delta -= 2.0 * std::f64::consts::PI;
+    } else if delta < -std::f64::consts::PI {
+        delta += 2.0 * std::f64::consts::PI;
+    }
+
+    (0..=ROUND_JOIN_STEPS)
+        .map(|step| {
+            let a = start_angle + delta * step as f64 / ROUND_JOIN_STEPS as f64;
+            let mut p = center;
+            p += Vector { x: a.cos(), y: a.sin() } * distance;
+            p
+        })
+        .collect()
+}
+
+/// Intersect the infinite lines through `p0` in direction `d0` and through
+/// `p1` in direction `d1`. Returns `None` for (near-)parallel lines.
+fn line_intersection(p0: Point, d0: Vector, p1: Point, d1: Vector) -> Option<Point> {
+    let denom = d0.x * d1.y - d0.y * d1.x;
+    if denom.abs() < f64::EPSILON {
+        return None;
+    }
+    let diff = p1 - p0;
+    let t = (diff.x * d1.y - diff.y * d1.x) / denom;
+    let mut result = p0;
+    result += d0 * t;
+    Some(result)
+}
+
+/// Strict interior intersection of the two segments, excluding intersections
+/// at or past either endpoint (so adjacent edges sharing a vertex don't
+/// count as crossing).
+pub(crate) fn segment_intersection(a0: Point, a1: Point, b0: Point, b1: Point) -> Option<Point> {
+    let d1 = a1 - a0;
+    let d2 = b1 - b0;
+    let denom = d1.x * d2.y - d1.y * d2.x;
+    if denom.abs() < f64::EPSILON {
+        return None;
+    }
+    let diff = b0 - a0;
+    let t = (diff.x * d2.y - diff.y * d2.x) / denom;
+    let u = (diff.x * d1.y - diff.y * d1.x) / denom;
+    if t > f64::EPSILON && t < 1.0 - f64::EPSILON && u > f64::EPSILON && u < 1.0 - f64::EPSILON {
+        let mut result = a0;
+        result += d1 * t;
+        Some(result)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A square's 90-degree corners are well within `MITER_LIMIT`, so each
+    /// should resolve to an exact miter join, inward by `distance` on both
+    /// axes.
+    #[test]
+    fn offsets_a_square_inward_with_miter_joins() {
+        let square = vec![
+            Point { x: -10.0, y: -10.0 },
+            Point { x: 10.0, y: -10.0 },
+            Point { x: 10.0, y: 10.0 },
+            Point { x: -10.0, y: 10.0 },
+        ];
+        let offset = offset_loop(&square, 2.0);
+        assert_eq!(offset.len(), 4);
+        for p in &offset {
+            assert!((p.x.abs() - 8.0).abs() < 1e-9);
+            assert!((p.y.abs() - 8.0).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use crate::offset::segment_intersection;
+use paths::coords::{Point, Vector};
+
+/// Fill rule used to decide which sub-loops of a self-intersecting cam
+/// profile loop belong to its boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WindingRule {
+    /// Keep sub-loops whose winding number relative to the whole path is
+    /// non-zero.
+    NonZero,
+    /// Keep sub-loops whose winding number relative to the whole path is
+    /// odd.
+    EvenOdd,
+}
+
+impl WindingRule {
+    fn keeps(&self, winding: i32) -> bool {
+        match self {
+            WindingRule::NonZero => winding != 0,
+            WindingRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Resolve a closed loop that may self-intersect (e.g. from a rotation
+/// sweep over tight curvature) into a set of simple boundary loops under
+/// `rule`.
+///
+/// The loop is recursively split at its self-intersection points into
+/// smaller closed sub-loops, then each sub-loop's signed winding number
+/// relative to the *original* path is computed from a representative
+/// interior point. Only sub-loops satisfying `rule` survive.
+pub fn cleanup_loop(loop_: &[Point], rule: WindingRule) -> Vec<Vec<Point>> {
+    split_at_self_intersections(loop_)
+        .into_iter()
+        .filter(|sub| rule.keeps(winding_number(loop_, interior_point(sub))))
+        .collect()
+}
+
+/// Split a closed loop at a single self-intersection into the two smaller
+/// loops it bounds, recursing on each until no crossing remains. Correct
+/// for loops whose self-intersections are transversal (the generic case
+/// for a rotation-swept cam profile).
+fn split_at_self_intersections(loop_: &[Point]) -> Vec<Vec<Point>> {
+    let Some((i, j, crossing)) = find_crossing(loop_) else {
+        return vec![loop_.to_vec()];
+    };
+
+    let n = loop_.len();
+    let mut first = vec![crossing];
+    first.extend_from_slice(&loop_[i + 1..=j]);
+    let mut second = vec![crossing];
+    second.extend_from_slice(&loop_[j + 1..n]);
+    second.extend_from_slice(&loop_[..=i]);
+
+    let mut result = split_at_self_intersections(&first);
+    result.extend(split_at_self_intersections(&second));
+    result
+}
+
+fn find_crossing(loop_: &[Point]) -> Option<(usize, usize, Point)> {
+    let n = loop_.len();
+    for i in 0..n {
+        for j in (i + 2)..n {
+            if i == 0 && j == n - 1 {
+                continue; // adjacent through the wrap-around
+            }
+            if let Some(p) =
+                segment_intersection(loop_[i], loop_[i + 1], loop_[j], loop_[(j + 1) % n])
+            {
+                return Some((i, j, p));
+            }
+        }
+    }
+    None
+}
+
+/// A point guaranteed to lie inside the (simple) sub-loop `loop_`.
+///
+/// The vertex centroid of a non-convex polygon routinely falls outside it,
+/// which is the common case for a cam lobe or notch, so instead nudge just
+/// off the midpoint of each edge along its inward normal and keep the first
+/// candidate that a ray-casting point-in-polygon test confirms is interior.
+fn interior_point(loop_: &[Point]) -> Point {
+    let n = loop_.len();
+    for i in 0..n {
+        let a = loop_[i];
+        let b = loop_[(i + 1) % n];
+        let edge = b - a;
+        let edge_len = edge.length();
+        if edge_len < f64::EPSILON {
+            continue;
+        }
+        let mid = (a + b) * 0.5;
+        let normal = Vector {
+            x: -edge.y,
+            y: edge.x,
+        } * (1.0 / edge_len);
+        let eps = edge_len * 1e-3;
+        for &sign in &[1.0, -1.0] {
+            let candidate = mid + normal * (eps * sign);
+            if point_in_polygon(loop_, candidate) {
+                return candidate;
+            }
+        }
+    }
+    // All edges degenerate (e.g. a single repeated point); nothing better
+    // to offer than the vertex average.
+    let sum = loop_
+        .iter()
+        .fold(Point { x: 0.0, y: 0.0 }, |acc, p| acc + *p);
+    sum * (1.0 / n as f64)
+}
+
+/// Even-odd ray-casting point-in-polygon test, used only to pick a genuine
+/// interior point for `interior_point` above; the actual fill rule applied
+/// to the result is `WindingRule`, computed separately by `winding_number`.
+fn point_in_polygon(loop_: &[Point], point: Point) -> bool {
+    let n = loop_.len();
+    let mut inside = false;
+    let mut j = n - 1;
+    for i in 0..n {
+        let pi = loop_[i];
+        let pj = loop_[j];
+        if (pi.y > point.y) != (pj.y > point.y)
+            && point.x < (pj.x - pi.x) * (point.y - pi.y) / (pj.y - pi.y) + pi.x
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Signed winding number of `loop_` around `point`, by the standard
+/// crossing-number algorithm (Sunday's winding number test).
+fn winding_number(loop_: &[Point], point: Point) -> i32 {
+    let n = loop_.len();
+    let mut winding = 0;
+    for i in 0..n {
+        let a = loop_[i];
+        let b = loop_[(i + 1) % n];
+        if a.y <= point.y {
+            if b.y > point.y && is_left(a, b, point) > 0.0 {
+                winding += 1;
+            }
+        } else if b.y <= point.y && is_left(a, b, point) < 0.0 {
+            winding -= 1;
+        }
+    }
+    winding
+}
+
+/// `> 0` if `p` is left of the line `a -> b`, `< 0` if right, `0` if on it.
+fn is_left(a: Point, b: Point, p: Point) -> f64 {
+    (b.x - a.x) * (p.y - a.y) - (p.x - a.x) * (b.y - a.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An L-shaped (non-convex) polygon whose plain vertex average lands in
+    /// the notch cut out of the L, outside the shape entirely — exactly the
+    /// case that made the old centroid-based interior point pick sub-loops
+    /// as empty.
+    #[test]
+    fn interior_point_finds_a_point_inside_a_non_convex_l_shape() {
+        let l_shape = vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 4.0, y: 0.0 },
+            Point { x: 4.0, y: 1.0 },
+            Point { x: 1.0, y: 1.0 },
+            Point { x: 1.0, y: 4.0 },
+            Point { x: 0.0, y: 4.0 },
+        ];
+
+        let vertex_average = Point {
+            x: 10.0 / 6.0,
+            y: 10.0 / 6.0,
+        };
+        assert!(!point_in_polygon(&l_shape, vertex_average));
+
+        assert!(point_in_polygon(&l_shape, interior_point(&l_shape)));
+    }
+
+    /// A self-intersecting bowtie splits into its two triangular lobes, each
+    /// surviving the `NonZero` rule.
+    #[test]
+    fn cleanup_loop_splits_a_bowtie_into_its_two_lobes() {
+        let bowtie = vec![
+            Point { x: 0.0, y: 0.0 },
+            Point { x: 2.0, y: 2.0 },
+            Point { x: 2.0, y: 0.0 },
+            Point { x: 0.0, y: 2.0 },
+        ];
+        let lobes = cleanup_loop(&bowtie, WindingRule::NonZero);
+        assert_eq!(lobes.len(), 2);
+        for lobe in &lobes {
+            assert_eq!(lobe.len(), 3);
+        }
+    }
+}